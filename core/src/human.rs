@@ -1,16 +1,182 @@
 use core::marker::PhantomData;
 
-use crate::vm::Token;
+extern crate alloc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::vm::{Block, NumberLiteral, NumberValue, StringLiteral, Token};
 use nom::error::{ErrorKind, ParseError};
 use nom::IResult;
 
 type Span<'a> = nom_locate::LocatedSpan<&'a str>;
 
+/// Parser-trace instrumentation for the combinators in this module.
+///
+/// Debugging the nom combinators here used to require manual `dbg!`. With the `trace`
+/// feature enabled, [`traced`] records each wrapped combinator's entry/exit, the
+/// `Span` offset it was tried at, and whether it matched, so a failed parse can be
+/// replayed as an indented call tree instead of stepped through by hand.
+#[cfg(feature = "trace")]
+pub mod trace {
+    extern crate alloc;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::cell::UnsafeCell;
+    use core::fmt::Write;
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    /// One recorded combinator entry (`matched: None`) or exit (`matched: Some(_)`).
+    #[derive(Debug, Clone)]
+    pub struct Event {
+        pub name: &'static str,
+        pub offset: usize,
+        pub depth: usize,
+        pub matched: Option<bool>,
+    }
+
+    /// A minimal spinlock-guarded log buffer. `no_std` leaves us without
+    /// `std::sync::Mutex`, so this hand-rolls the same guarantee with a spin loop
+    /// over an `AtomicBool`: `lock()` only ever hands out a `LogGuard` once the
+    /// previous one has been dropped, so concurrent tracing from multiple threads
+    /// is merely serialized, not a data race.
+    struct Log {
+        locked: AtomicBool,
+        state: UnsafeCell<(Vec<Event>, usize)>,
+    }
+
+    // Safety: all access to `state` goes through `lock()`, which spins until it has
+    // exclusive access, so `Log` is genuinely safe to share across threads.
+    unsafe impl Sync for Log {}
+
+    impl Log {
+        fn lock(&self) -> LogGuard<'_> {
+            while self
+                .locked
+                .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+            {
+                core::hint::spin_loop();
+            }
+            LogGuard { log: self }
+        }
+    }
+
+    struct LogGuard<'a> {
+        log: &'a Log,
+    }
+
+    impl core::ops::Deref for LogGuard<'_> {
+        type Target = (Vec<Event>, usize);
+        fn deref(&self) -> &Self::Target {
+            // Safety: holding a `LogGuard` means `lock()` won the spin, so no other
+            // guard can exist at the same time.
+            unsafe { &*self.log.state.get() }
+        }
+    }
+
+    impl core::ops::DerefMut for LogGuard<'_> {
+        fn deref_mut(&mut self) -> &mut Self::Target {
+            // Safety: see `Deref` above.
+            unsafe { &mut *self.log.state.get() }
+        }
+    }
+
+    impl Drop for LogGuard<'_> {
+        fn drop(&mut self) {
+            self.log.locked.store(false, Ordering::Release);
+        }
+    }
+
+    static LOG: Log = Log {
+        locked: AtomicBool::new(false),
+        state: UnsafeCell::new((Vec::new(), 0)),
+    };
+
+    pub(crate) fn enter(name: &'static str, offset: usize) {
+        let mut state = LOG.lock();
+        let depth = state.1;
+        state.1 += 1;
+        state.0.push(Event {
+            name,
+            offset,
+            depth,
+            matched: None,
+        });
+    }
+
+    pub(crate) fn exit(name: &'static str, offset: usize, matched: bool) {
+        let mut state = LOG.lock();
+        state.1 -= 1;
+        let depth = state.1;
+        state.0.push(Event {
+            name,
+            offset,
+            depth,
+            matched: Some(matched),
+        });
+    }
+
+    /// Drains the recorded trace events, in entry/exit order, so a caller (e.g. the
+    /// REPL) can render the call tree alongside the diagnostic for a failed parse.
+    pub fn take_log() -> Vec<Event> {
+        core::mem::take(&mut LOG.lock().0)
+    }
+
+    /// Renders a drained log as an indented call tree, one line per event.
+    pub fn render(log: &[Event]) -> String {
+        let mut out = String::new();
+        for event in log {
+            let indent = "  ".repeat(event.depth);
+            match event.matched {
+                None => {
+                    let _ = writeln!(out, "{indent}{} @{}", event.name, event.offset);
+                }
+                Some(matched) => {
+                    let verdict = if matched { "matched" } else { "failed" };
+                    let _ = writeln!(out, "{indent}{} @{} -> {verdict}", event.name, event.offset);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Wraps `inner` to record its entry/exit under `name` when the `trace` feature is
+/// enabled; a transparent passthrough (so the combinators compile exactly as today)
+/// when it isn't.
+#[cfg(feature = "trace")]
+fn traced<'a, F, O, E: ParseError<Span<'a>>>(
+    name: &'static str,
+    mut inner: F,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E>
+where
+    F: FnMut(Span<'a>) -> IResult<Span<'a>, O, E>,
+{
+    move |input: Span<'a>| {
+        let offset = input.location_offset();
+        trace::enter(name, offset);
+        let result = inner(input);
+        trace::exit(name, offset, result.is_ok());
+        result
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+fn traced<'a, F, O, E: ParseError<Span<'a>>>(
+    _name: &'static str,
+    inner: F,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E>
+where
+    F: FnMut(Span<'a>) -> IResult<Span<'a>, O, E>,
+{
+    inner
+}
+
 /// Consumes a slash-slash-comment-eol, transforming it into the empty string.
 fn eol_comment<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
     use nom::bytes::complete::{is_not, tag};
     use nom::sequence::preceded;
-    preceded(tag("//"), is_not("\n\r"))(i)
+    traced("eol_comment", preceded(tag("//"), is_not("\n\r")))(i)
 }
 
 /// Matches whitespace or eol comments across multiple lines.
@@ -22,6 +188,7 @@ fn ws_or_eol<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, (),
 }
 
 /// Consumes leading and trailing whitespace and comments, returning the output of `inner`.
+#[cfg(test)]
 fn ws<'a, F, O, E: ParseError<Span<'a>>>(
     inner: F,
 ) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, O, E>
@@ -29,18 +196,359 @@ where
     F: FnMut(Span<'a>) -> IResult<Span<'a>, O, E>,
 {
     use nom::sequence::delimited;
-    delimited(ws_or_eol, inner, ws_or_eol)
+    traced("ws", delimited(ws_or_eol, inner, ws_or_eol))
 }
 
 fn word<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
     use nom::character::complete::alpha1;
-    let (rem, all) = alpha1(input)?;
-    Ok((rem, Token::Word(all)))
+    traced("word", |input| {
+        let (rem, all) = alpha1(input)?;
+        Ok((rem, Token::Word(all)))
+    })(input)
+}
+
+/// Matches `prefix` (case-insensitively) followed by one or more digits accepted by
+/// `is_digit`, returning the whole matched span (prefix included).
+fn radix_digits<'a, E: ParseError<Span<'a>>>(
+    prefix: &'static str,
+    is_digit: fn(char) -> bool,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
+    use nom::bytes::complete::{tag_no_case, take_while1};
+    use nom::combinator::recognize;
+    use nom::sequence::preceded;
+    recognize(preceded(tag_no_case(prefix), take_while1(is_digit)))
+}
+
+fn hex_literal<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
+    radix_digits("0x", |c: char| c.is_ascii_hexdigit())(i)
+}
+
+fn oct_literal<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
+    radix_digits("0o", |c: char| ('0'..='7').contains(&c))(i)
+}
+
+fn bin_literal<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
+    radix_digits("0b", |c: char| c == '0' || c == '1')(i)
+}
+
+/// Matches a decimal integer or floating point literal shape, e.g. `1`, `1.5`, `1e-9`.
+fn decimal_literal<'a, E: ParseError<Span<'a>>>(i: Span<'a>) -> IResult<Span<'a>, Span<'a>, E> {
+    use nom::character::complete::{char, digit1, one_of};
+    use nom::combinator::{opt, recognize};
+    use nom::sequence::tuple;
+    recognize(tuple((
+        digit1,
+        opt(tuple((char('.'), digit1))),
+        opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+    )))(i)
+}
+
+/// Parses the matched shape of a numeric literal (radix prefix and all) into the value
+/// it denotes. This is the second phase of the two-phase lexical-core-style approach:
+/// `number` below only establishes that the text is shaped like a literal, this turns
+/// that text into an actual `NumberValue`, so overflow is a parse error rather than a
+/// tokenizing failure.
+fn parse_number_value(text: &str) -> Result<NumberValue, ()> {
+    if let Some(digits) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return i64::from_str_radix(digits, 16)
+            .map(NumberValue::Int)
+            .map_err(|_| ());
+    }
+    if let Some(digits) = text.strip_prefix("0o").or_else(|| text.strip_prefix("0O")) {
+        return i64::from_str_radix(digits, 8)
+            .map(NumberValue::Int)
+            .map_err(|_| ());
+    }
+    if let Some(digits) = text.strip_prefix("0b").or_else(|| text.strip_prefix("0B")) {
+        return i64::from_str_radix(digits, 2)
+            .map(NumberValue::Int)
+            .map_err(|_| ());
+    }
+    if text.contains('.') || text.contains('e') || text.contains('E') {
+        return text.parse::<f64>().map(NumberValue::Float).map_err(|_| ());
+    }
+    text.parse::<i64>().map(NumberValue::Int).map_err(|_| ())
+}
+
+fn number<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
+    use nom::branch::alt;
+    use nom::character::complete::alphanumeric0;
+    use nom::sequence::pair;
+
+    traced("number", |input| {
+        let (rem, (shape, suffix)) = pair(
+            alt((hex_literal, oct_literal, bin_literal, decimal_literal)),
+            alphanumeric0,
+        )(input)?;
+
+        // A bare `0` is fine, as is a radix-prefixed literal (`0x1A`, `0o7`, `0b1`) or a
+        // float with a leading `0.` (`0.5`), but a second leading digit like `007` is
+        // ambiguous with the octal-by-leading-zero convention some languages use, so we
+        // reject it outright rather than silently reading it as decimal.
+        let shape_bytes = shape.fragment().as_bytes();
+        if shape_bytes.first() == Some(&b'0') && shape_bytes.get(1).is_some_and(u8::is_ascii_digit)
+        {
+            return Err(nom::Err::Failure(E::from_error_kind(shape, ErrorKind::Digit)));
+        }
+
+        let value = parse_number_value(shape.fragment())
+            .map_err(|_| nom::Err::Failure(E::from_error_kind(shape, ErrorKind::TooLarge)))?;
+
+        Ok((
+            rem,
+            Token::Number(NumberLiteral {
+                value,
+                suffix: if suffix.fragment().is_empty() {
+                    None
+                } else {
+                    Some(suffix)
+                },
+                span: shape,
+            }),
+        ))
+    })(input)
+}
+
+/// Decodes the escapes in a quoted string literal's raw text (quotes included),
+/// returning the byte offset of the offending backslash on the first bad escape.
+fn decode_string_escapes(raw: &str) -> Result<String, usize> {
+    let inner = &raw[1..raw.len() - 1];
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        let escape_start = 1 + i;
+        match chars.next() {
+            Some((_, 'n')) => value.push('\n'),
+            Some((_, 't')) => value.push('\t'),
+            Some((_, '\\')) => value.push('\\'),
+            Some((_, '"')) => value.push('"'),
+            Some((_, 'u')) => {
+                if chars.peek().map(|&(_, c)| c) != Some('{') {
+                    return Err(escape_start);
+                }
+                chars.next();
+                let mut hex = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, h)) if h.is_ascii_hexdigit() => hex.push(h),
+                        _ => return Err(escape_start),
+                    }
+                }
+                let code = u32::from_str_radix(&hex, 16).map_err(|_| escape_start)?;
+                value.push(char::from_u32(code).ok_or(escape_start)?);
+            }
+            _ => return Err(escape_start),
+        }
+    }
+    Ok(value)
+}
+
+fn string_literal<'a, E: ParseError<Span<'a>>>(
+    input: Span<'a>,
+) -> IResult<Span<'a>, Token<Span<'a>>, E> {
+    use nom::bytes::complete::tag;
+    use nom::Slice;
+
+    traced("string_literal", |input| {
+        let (after_open, _) = tag("\"")(input)?;
+
+        // Scanned by hand (honoring `\"` escapes) instead of `delimited(.., many0(..),
+        // tag("\""))`: `nom::bytes::complete` combinators can never report
+        // `Incomplete`, so relying on `tag("\"")` to fail would surface an unterminated
+        // string as an ordinary `Err::Error` pointing at the end of input — which
+        // `token`'s `alt` then discards in favor of a later alternative's unrelated
+        // error. Reporting `ErrorKind::Eof` directly, as a `Failure`, matches how
+        // `block` reports an unterminated `#+BEGIN_...` and lets callers (e.g. the
+        // REPL's line-continuation check) recognize it as "needs more input".
+        let body = *after_open.fragment();
+        let mut close = None;
+        let mut chars = body.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => {
+                    close = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        let close = match close {
+            Some(close) => close,
+            None => return Err(nom::Err::Failure(E::from_error_kind(input, ErrorKind::Eof))),
+        };
+
+        let raw = input.slice(..close + 2);
+        let rem = after_open.slice(close + 1..);
+
+        match decode_string_escapes(raw.fragment()) {
+            Ok(value) => Ok((rem, Token::Str(StringLiteral { value, span: raw }))),
+            Err(offset) => Err(nom::Err::Failure(E::from_error_kind(
+                raw.slice(offset..),
+                ErrorKind::EscapedTransform,
+            ))),
+        }
+    })(input)
+}
+
+/// Multi-character operators are tried before their single-character prefixes
+/// (e.g. `==` before `=`), so `alt` below is ordered longest-match-first.
+fn op<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
+    use nom::branch::alt;
+    use nom::bytes::complete::tag;
+    use nom::combinator::recognize;
+    traced(
+        "op",
+        recognize(alt((
+            alt((
+                tag("=="),
+                tag("!="),
+                tag("<="),
+                tag(">="),
+                tag("&&"),
+                tag("||"),
+                tag("->"),
+                tag("=>"),
+                tag(".."),
+            )),
+            alt((
+                tag("+"),
+                tag("-"),
+                tag("*"),
+                tag("/"),
+                tag("%"),
+                tag("="),
+                tag("<"),
+                tag(">"),
+                tag("!"),
+                tag("&"),
+                tag("|"),
+                tag("^"),
+            )),
+        ))),
+    )(input)
+    .map(|(rem, matched)| (rem, Token::Op(matched)))
+}
+
+fn punct<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
+    use nom::character::complete::one_of;
+    use nom::combinator::recognize;
+    traced("punct", recognize(one_of("(){}[],;:.")))(input)
+        .map(|(rem, matched)| (rem, Token::Punct(matched)))
+}
+
+/// Parses the opening delimiter of an org-style block: `#+BEGIN_<name>`, an optional
+/// argument line, and the newline (or end of input) that ends the header.
+fn block_begin<'a, E: ParseError<Span<'a>>>(
+    i: Span<'a>,
+) -> IResult<Span<'a>, (Span<'a>, Option<Span<'a>>), E> {
+    use nom::branch::alt;
+    use nom::bytes::complete::{is_not, tag_no_case};
+    use nom::character::complete::{alpha1, line_ending, space0};
+    use nom::combinator::{eof, opt};
+
+    let (i, _) = tag_no_case("#+begin_")(i)?;
+    let (i, name) = alpha1(i)?;
+    let (i, _) = space0(i)?;
+    let (i, args) = opt(is_not("\r\n"))(i)?;
+    // A header with nothing after it is still a block opener, so `block` can report
+    // it as unterminated rather than as a non-block.
+    let (i, _) = alt((line_ending, eof))(i)?;
+    Ok((i, (name, args)))
+}
+
+/// Splits `body` at the line holding a case-insensitive `#+END_<name>` matching
+/// `name`, returning the span before that line and the remaining input after it.
+/// Returns `None` if no matching closer is found, i.e. the block is unterminated.
+fn find_block_end<'a>(body: Span<'a>, name: &str) -> Option<(Span<'a>, Span<'a>)> {
+    use nom::Slice;
+
+    let closer = alloc::format!("#+end_{}", name.to_ascii_lowercase());
+    let text = *body.fragment();
+    let mut offset = 0usize;
+    for line in text.split_inclusive('\n') {
+        if line.trim().to_ascii_lowercase() == closer {
+            let after = offset + line.len();
+            return Some((body.slice(..offset), body.slice(after..)));
+        }
+        offset += line.len();
+    }
+    None
+}
+
+/// Strips a run of leading and a run of trailing blank lines from `contents`,
+/// returning the trimmed span and how many lines were stripped from each end, so the
+/// original spacing can be reconstructed exactly (org's pre/post-blank counts).
+fn trim_blank_lines(contents: Span) -> (Span, usize, usize) {
+    use nom::Slice;
+
+    let text = *contents.fragment();
+    let mut lines = Vec::new();
+    let mut pos = 0usize;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let len = match rest.find('\n') {
+            Some(nl) => nl + 1,
+            None => rest.len(),
+        };
+        lines.push((rest[..len].trim().is_empty(), len));
+        pos += len;
+    }
+
+    let mut start_idx = 0;
+    while start_idx < lines.len() && lines[start_idx].0 {
+        start_idx += 1;
+    }
+    let mut end_idx = lines.len();
+    while end_idx > start_idx && lines[end_idx - 1].0 {
+        end_idx -= 1;
+    }
+
+    let start: usize = lines[..start_idx].iter().map(|(_, len)| len).sum();
+    let kept: usize = lines[start_idx..end_idx].iter().map(|(_, len)| len).sum();
+
+    (
+        contents.slice(start..start + kept),
+        start_idx,
+        lines.len() - end_idx,
+    )
+}
+
+fn block<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
+    traced("block", |input| {
+        let (rem, (name, args)) = block_begin(input)?;
+
+        match find_block_end(rem, name.fragment()) {
+            Some((body, after)) => {
+                let (contents, pre_blank, post_blank) = trim_blank_lines(body);
+                Ok((
+                    after,
+                    Token::Block(Block {
+                        name,
+                        args,
+                        contents,
+                        pre_blank,
+                        post_blank,
+                    }),
+                ))
+            }
+            // Unterminated block: point the diagnostic at the opening delimiter.
+            None => Err(nom::Err::Failure(E::from_error_kind(input, ErrorKind::Eof))),
+        }
+    })(input)
 }
 
 fn token<'a, E: ParseError<Span<'a>>>(input: Span<'a>) -> IResult<Span<'a>, Token<Span<'a>>, E> {
     use nom::branch::alt;
-    alt((word,))(input)
+    traced("token", alt((block, number, string_literal, word, op, punct)))(input)
 }
 
 pub struct Tokenizer<'a, E> {
@@ -62,25 +570,42 @@ impl<'a, E: ParseError<Span<'a>>> Iterator for Tokenizer<'a, E> {
         if self.done {
             return None;
         }
-        let res = match ws(token)(self.input) {
-            Ok((remaining, token)) => {
-                self.done = remaining.len() == 0;
+
+        // Stripping leading trivia up front (this can never fail) lets us tell
+        // "nothing left to tokenize" (end of stream, not an error) apart from "the
+        // remaining input doesn't start a token", without pattern-matching `(rem, e)`
+        // out of the error below. That tuple-destructure was forcing `token` (and
+        // everything under it — `alt`, `string_literal`, ...) to run with nom's
+        // internal `(Span, ErrorKind)` error representation instead of this iterator's
+        // own `E`, so a caller's `ParseError::append` was never actually invoked as an
+        // error unwound back out through nested `alt`s — the "while parsing ..."
+        // context stack stayed permanently empty. `token` is called directly below
+        // (rather than through `ws`, which would re-strip the same leading trivia a
+        // second time) with an explicit type annotation pinning its `E` to this
+        // iterator's own `E`, so `append` now runs for real.
+        let (rest, ()) = ws_or_eol::<E>(self.input).unwrap_or((self.input, ()));
+        if rest.fragment().is_empty() {
+            self.done = true;
+            return None;
+        }
+
+        let res: IResult<Span<'a>, Token<Span<'a>>, E> = token(rest);
+        Some(match res {
+            Ok((remaining, tok)) => {
+                let (remaining, ()) = ws_or_eol::<E>(remaining).unwrap_or((remaining, ()));
+                self.done = remaining.fragment().is_empty();
                 self.input = remaining;
-                Ok(token)
+                Ok(tok)
             }
             Err(Incomplete(_)) => {
                 self.done = true;
-                Err(E::from_error_kind(self.input, ErrorKind::Eof))
+                Err(E::from_error_kind(rest, ErrorKind::Eof))
             }
-            Err(Error((rem, e))) | Err(Failure((rem, e))) => {
-                if rem.len() == 0 {
-                    return None;
-                }
+            Err(Error(e)) | Err(Failure(e)) => {
                 self.done = true;
-                Err(E::from_error_kind(rem, e))
+                Err(e)
             }
-        };
-        Some(res)
+        })
     }
 }
 
@@ -91,37 +616,224 @@ mod test {
     use nom::error::Error;
     use std::vec::Vec;
 
+    type TestError<'a> = Error<Span<'a>>;
+
+    /// Runs a parser over `input` wrapped in a fresh `Span`, projecting the
+    /// remaining input back down to a plain `&str` so assertions don't have to
+    /// fuss with `Span`'s offset/line/column bookkeeping.
+    fn parse<'a, O>(
+        mut f: impl FnMut(Span<'a>) -> IResult<Span<'a>, O, TestError<'a>>,
+        input: &'a str,
+    ) -> Result<(&'a str, O), nom::Err<TestError<'a>>> {
+        f(Span::new(input)).map(|(rem, out)| (*rem.fragment(), out))
+    }
+
+    /// Projects a parsed `Token<Span>` down to a `Token<&str>`, discarding the
+    /// position info `Span` carries, so it can be compared against a plain
+    /// string-literal expectation.
+    fn frag(token: Token<Span<'_>>) -> Token<&str> {
+        match token {
+            Token::Any => Token::Any,
+            Token::Word(s) => Token::Word(*s.fragment()),
+            Token::Number(n) => Token::Number(NumberLiteral {
+                value: n.value,
+                suffix: n.suffix.map(|s| *s.fragment()),
+                span: *n.span.fragment(),
+            }),
+            Token::Str(s) => Token::Str(StringLiteral {
+                value: s.value,
+                span: *s.span.fragment(),
+            }),
+            Token::Punct(s) => Token::Punct(*s.fragment()),
+            Token::Op(s) => Token::Op(*s.fragment()),
+            Token::Block(b) => Token::Block(Block {
+                name: *b.name.fragment(),
+                args: b.args.map(|s| *s.fragment()),
+                contents: *b.contents.fragment(),
+                pre_blank: b.pre_blank,
+                post_blank: b.post_blank,
+            }),
+        }
+    }
+
     #[test]
     fn word_test() {
-        let word = word::<Error<&str>>;
-        assert_eq!(word("hello"), Ok(("", Token::Word("hello"))));
+        assert_eq!(
+            parse(word, "hello").map(|(r, t)| (r, frag(t))),
+            Ok(("", Token::Word("hello")))
+        );
     }
 
     #[test]
     fn ws_test() {
-        let word = word::<Error<&str>>;
-        assert_eq!(ws(word)("   hello"), Ok(("", Token::Word("hello"))));
-        assert_eq!(ws(word)("hello   "), Ok(("", Token::Word("hello"))));
-        assert_eq!(ws(word)("   hello   "), Ok(("", Token::Word("hello"))));
+        assert_eq!(
+            parse(ws(word), "   hello").map(|(r, t)| (r, frag(t))),
+            Ok(("", Token::Word("hello")))
+        );
+        assert_eq!(
+            parse(ws(word), "hello   ").map(|(r, t)| (r, frag(t))),
+            Ok(("", Token::Word("hello")))
+        );
+        assert_eq!(
+            parse(ws(word), "   hello   ").map(|(r, t)| (r, frag(t))),
+            Ok(("", Token::Word("hello")))
+        );
     }
 
     #[test]
     fn eol_comment_test() {
-        let word = word::<Error<&str>>;
         const HELLO: Token<&str> = Token::Word("hello");
-        assert_eq!(ws(word)("hello //test"), Ok(("", HELLO)));
-        assert_eq!(ws(word)(" hello //test there"), Ok(("", HELLO)));
-        assert_eq!(ws(word)(" //test\nhello"), Ok(("", HELLO)));
-        assert_eq!(ws(word)("//test\nhello"), Ok(("", HELLO)));
-        assert_eq!(ws(word)("//test\n//test\nhello"), Ok(("", HELLO)));
-        assert_eq!(ws(word)("//test\n//test\n hello"), Ok(("", HELLO)));
+        assert_eq!(
+            parse(ws(word), "hello //test").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+        assert_eq!(
+            parse(ws(word), " hello //test there").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+        assert_eq!(
+            parse(ws(word), " //test\nhello").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+        assert_eq!(
+            parse(ws(word), "//test\nhello").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+        assert_eq!(
+            parse(ws(word), "//test\n//test\nhello").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+        assert_eq!(
+            parse(ws(word), "//test\n//test\n hello").map(|(r, t)| (r, frag(t))),
+            Ok(("", HELLO))
+        );
+    }
+
+    #[test]
+    fn number_test() {
+        assert_eq!(
+            parse(number, "42").map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Number(NumberLiteral {
+                    value: NumberValue::Int(42),
+                    suffix: None,
+                    span: "42",
+                })
+            ))
+        );
+        assert_eq!(
+            parse(number, "0x2A").map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Number(NumberLiteral {
+                    value: NumberValue::Int(42),
+                    suffix: None,
+                    span: "0x2A",
+                })
+            ))
+        );
+        assert_eq!(
+            parse(number, "1.5e2f32").map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Number(NumberLiteral {
+                    value: NumberValue::Float(150.0),
+                    suffix: Some("f32"),
+                    span: "1.5e2",
+                })
+            ))
+        );
+        assert!(parse(number, "99999999999999999999").is_err());
+        assert!(parse(number, "007").is_err());
+        assert_eq!(
+            parse(number, "0.5").map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Number(NumberLiteral {
+                    value: NumberValue::Float(0.5),
+                    suffix: None,
+                    span: "0.5",
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn string_test() {
+        assert_eq!(
+            parse(string_literal, r#""hello""#).map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Str(StringLiteral {
+                    value: "hello".into(),
+                    span: r#""hello""#,
+                })
+            ))
+        );
+        assert_eq!(
+            parse(string_literal, r#""a\tb\n\u{263A}""#).map(|(r, t)| (r, frag(t))),
+            Ok((
+                "",
+                Token::Str(StringLiteral {
+                    value: "a\tb\n\u{263A}".into(),
+                    span: r#""a\tb\n\u{263A}""#,
+                })
+            ))
+        );
+        assert!(parse(string_literal, r#""bad \q escape""#).is_err());
+
+        // An unterminated string reports `ErrorKind::Eof`, like an unterminated
+        // block does, rather than the generic tag-mismatch error `delimited`+`tag`
+        // would otherwise report — so callers (e.g. the REPL's line-continuation
+        // check) can recognize it as "needs more input" instead of a hard error.
+        match parse(string_literal, "\"unterminated") {
+            Err(nom::Err::Failure(e)) => assert_eq!(e.code, ErrorKind::Eof),
+            other => panic!("expected an Eof failure, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn op_punct_test() {
+        assert_eq!(
+            parse(op, "==").map(|(r, t)| (r, frag(t))),
+            Ok(("", Token::Op("==")))
+        );
+        assert_eq!(
+            parse(op, "=x").map(|(r, t)| (r, frag(t))),
+            Ok(("x", Token::Op("=")))
+        );
+        assert_eq!(
+            parse(punct, "(x").map(|(r, t)| (r, frag(t))),
+            Ok(("x", Token::Punct("(")))
+        );
+    }
+
+    #[test]
+    fn block_test() {
+        assert_eq!(
+            parse(block, "#+BEGIN_shell echo hi\n\nls -la\n\n#+END_shell\nrest")
+                .map(|(r, t)| (r, frag(t))),
+            Ok((
+                "rest",
+                Token::Block(Block {
+                    name: "shell",
+                    args: Some("echo hi"),
+                    contents: "ls -la\n",
+                    pre_blank: 1,
+                    post_blank: 1,
+                })
+            ))
+        );
+        assert!(parse(block, "#+BEGIN_shell\nls -la\n").is_err());
     }
 
     #[test]
     fn tokenize_test() {
-        let tokenize = |i| {
-            let res: Vec<Result<Token<&str>, _>> = tokenize::<Error<&str>>(i).collect();
-            res
+        let tokenize = |i: &'static str| -> Vec<Result<Token<&str>, ()>> {
+            tokenize::<TestError>(Span::new(i))
+                .map(|res| res.map(frag).map_err(|_| ()))
+                .collect()
         };
         assert_eq!(tokenize("hello //test"), [Ok(Token::Word("hello"))]);
         assert_eq!(
@@ -1,27 +1,125 @@
+extern crate alloc;
+
+use alloc::string::String;
 use core::mem::MaybeUninit;
 use fixed_slice_vec::FixedSliceVec;
 
+/// A lexical token produced by a front-end tokenizer (e.g. [`crate::human`]).
+///
+/// `T` is the underlying source-text representation used for variants that
+/// only need to remember *where* they came from (typically a `Span`).
 #[derive(Debug, PartialEq)]
-pub enum Token {
+pub enum Token<T> {
     Any,
+    Word(T),
+    Number(NumberLiteral<T>),
+    Str(StringLiteral<T>),
+    Punct(T),
+    Op(T),
+    Block(Block<T>),
+}
+
+/// A numeric literal, split into the value it denotes and the raw text it
+/// was parsed from.
+///
+/// `span` covers the digits (and radix prefix, if any) but not the
+/// `suffix`, which is kept separately so callers can validate it
+/// independently (e.g. reject an unknown type suffix).
+#[derive(Debug, PartialEq)]
+pub struct NumberLiteral<T> {
+    pub value: NumberValue,
+    pub suffix: Option<T>,
+    pub span: T,
+}
+
+/// The value a numeric literal denotes, once the matched text has been
+/// parsed by the second phase of the lexer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberValue {
+    Int(i64),
+    Float(f64),
+}
+
+/// A string literal, holding both the escape-decoded value and the raw
+/// (still-quoted) source text it was parsed from.
+#[derive(Debug, PartialEq)]
+pub struct StringLiteral<T> {
+    pub value: String,
+    pub span: T,
+}
+
+/// An org-style delimited raw block: `#+BEGIN_<name> [args]` ... `#+END_<name>`.
+///
+/// `contents` is a raw span that is *not* tokenized as recap source (shell snippets,
+/// data, literal comments). Runs of leading/trailing blank lines are stripped from each
+/// end and counted separately in `pre_blank`/`post_blank`, so embedded content
+/// round-trips exactly even though `contents` itself is trimmed.
+#[derive(Debug, PartialEq)]
+pub struct Block<T> {
+    pub name: T,
+    pub args: Option<T>,
+    pub contents: T,
+    pub pre_blank: usize,
+    pub post_blank: usize,
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum MemoryCell {}
 
+/// Where a [`Machine`] sends its textual output.
+///
+/// Decoupling the VM from a concrete output channel lets the REPL capture output into
+/// its own `codespan` writer and lets integration tests assert on captured text,
+/// instead of the VM calling `println!` directly at the call site.
+pub trait Host {
+    fn stdout(&mut self, text: &str);
+    fn stderr(&mut self, text: &str);
+}
+
+impl<H: Host + ?Sized> Host for alloc::boxed::Box<H> {
+    fn stdout(&mut self, text: &str) {
+        (**self).stdout(text)
+    }
+    fn stderr(&mut self, text: &str) {
+        (**self).stderr(text)
+    }
+}
+
+/// A [`Host`] that writes straight to the process' standard streams.
+///
+/// Only available with the `std` feature, since writing to real file descriptors
+/// needs an OS; embedded hosts should implement [`Host`] themselves instead.
+#[cfg(feature = "std")]
+pub struct BasicHost;
+
+#[cfg(feature = "std")]
+impl Host for BasicHost {
+    fn stdout(&mut self, text: &str) {
+        extern crate std;
+        std::print!("{text}");
+    }
+    fn stderr(&mut self, text: &str) {
+        extern crate std;
+        std::eprint!("{text}");
+    }
+}
+
 pub struct Machine<'a> {
     memory: FixedSliceVec<'a, MemoryCell>,
     //stacks: FixedSliceVec<'a, FixedSliceVec<'a, MemoryCell>>,
+    host: &'a mut dyn Host,
 }
 
 impl<'a> Machine<'a> {
     pub fn new(
         memory: &'a mut [MaybeUninit<MemoryCell>],
         //stacks: &'a mut [MaybeUninit<FixedSliceVec<'a, MemoryCell>>],
+        host: &'a mut dyn Host,
     ) -> Self {
         Self {
             memory: FixedSliceVec::new(memory),
             //stacks: FixedSliceVec::new(stacks),
+            host,
         }
     }
 }
@@ -0,0 +1,278 @@
+//! A trie-based keymap: textual key-sequence specs (e.g. `"C-x C-s"`, `"<alt>n"`) are
+//! parsed into chords and inserted into a trie whose leaves are editor commands, so
+//! multi-key sequences resolve by walking the trie as events arrive instead of being
+//! limited to the two hardcoded `rl.bind_sequence` aliases.
+
+use std::fmt;
+
+use rustyline::history::History;
+use rustyline::{Cmd, Editor, Event, KeyCode, KeyEvent, Modifiers};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum KeymapError {
+    /// Could not parse the spec itself (unknown key name, empty chord, ...).
+    Parse(String),
+    /// A shorter binding already terminates in a command somewhere along this
+    /// spec's path, so the new (longer) chord could never be reached.
+    KeyPathBlocked,
+    /// This exact key sequence is already bound to a command.
+    KeyAlreadySet,
+    /// This spec is a strict prefix of an existing, longer chord, so binding it here
+    /// would make that longer chord unreachable.
+    KeyPathShadows,
+}
+
+impl fmt::Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeymapError::Parse(msg) => write!(f, "{msg}"),
+            KeymapError::KeyPathBlocked => {
+                write!(f, "a shorter binding already occupies a prefix of this chord")
+            }
+            KeymapError::KeyAlreadySet => write!(f, "this key sequence is already bound"),
+            KeymapError::KeyPathShadows => {
+                write!(f, "this chord is a prefix of a longer existing binding")
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    command: Option<Cmd>,
+    children: Vec<(KeyEvent, Node)>,
+}
+
+impl Node {
+    fn child_index(&self, key: &KeyEvent) -> Option<usize> {
+        self.children.iter().position(|(k, _)| k == key)
+    }
+}
+
+/// A trie of key chords mapping to editor commands.
+#[derive(Default)]
+pub struct Keymap {
+    root: Node,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds the key sequence described by `spec` (e.g. `"C-x C-s"`) to `command`.
+    pub fn bind(&mut self, spec: &str, command: Cmd) -> Result<(), KeymapError> {
+        let chords = parse_sequence(spec)?;
+        let (first, rest) = chords
+            .split_first()
+            .ok_or_else(|| KeymapError::Parse("empty key sequence".into()))?;
+
+        let mut node = &mut self.root;
+        for key in core::iter::once(first).chain(rest) {
+            if node.command.is_some() {
+                return Err(KeymapError::KeyPathBlocked);
+            }
+            let idx = match node.child_index(key) {
+                Some(idx) => idx,
+                None => {
+                    node.children.push((*key, Node::default()));
+                    node.children.len() - 1
+                }
+            };
+            node = &mut node.children[idx].1;
+        }
+
+        if node.command.is_some() {
+            return Err(KeymapError::KeyAlreadySet);
+        }
+        if !node.children.is_empty() {
+            return Err(KeymapError::KeyPathShadows);
+        }
+        node.command = Some(command);
+        Ok(())
+    }
+
+    /// Registers every bound chord against a rustyline `Editor`. Each chord is
+    /// installed as an `Event::KeySeq` carrying its full key path, so a multi-key
+    /// chord like `C-x C-l` resolves key-by-key exactly as rustyline resolves any
+    /// other multi-key binding, rather than being collapsed onto its first key.
+    pub fn register<H: rustyline::Helper, I: History>(&self, rl: &mut Editor<H, I>) {
+        register_node(&self.root, &mut Vec::new(), rl);
+    }
+}
+
+/// Walks `node`'s subtree depth-first, registering a binding for every leaf using
+/// the full key path (`path` plus the keys above it) accumulated on the way down.
+fn register_node<H: rustyline::Helper, I: History>(
+    node: &Node,
+    path: &mut Vec<KeyEvent>,
+    rl: &mut Editor<H, I>,
+) {
+    if let Some(command) = &node.command {
+        rl.bind_sequence(Event::KeySeq(path.clone()), command.clone());
+    }
+    for (key, child) in &node.children {
+        path.push(*key);
+        register_node(child, path, rl);
+        path.pop();
+    }
+}
+
+/// Parses a whitespace-separated sequence of chords, e.g. `"C-x C-s"` or `"<alt>n"`.
+fn parse_sequence(spec: &str) -> Result<Vec<KeyEvent>, KeymapError> {
+    spec.split_whitespace().map(parse_chord).collect()
+}
+
+/// Parses a single chord such as `"C-x"`, `"M-n"`, or `"<alt>n"` into a `KeyEvent`.
+fn parse_chord(token: &str) -> Result<KeyEvent, KeymapError> {
+    let mut modifiers = Modifiers::NONE;
+    let mut rest = token;
+    loop {
+        rest = if let Some(rest) = rest.strip_prefix("C-").or_else(|| rest.strip_prefix("<ctrl>")) {
+            modifiers |= Modifiers::CTRL;
+            rest
+        } else if let Some(rest) = rest.strip_prefix("M-").or_else(|| rest.strip_prefix("<alt>")) {
+            modifiers |= Modifiers::ALT;
+            rest
+        } else if let Some(rest) = rest.strip_prefix("S-").or_else(|| rest.strip_prefix("<shift>")) {
+            modifiers |= Modifiers::SHIFT;
+            rest
+        } else {
+            break;
+        };
+    }
+    Ok(KeyEvent(parse_keycode(rest)?, modifiers))
+}
+
+fn parse_keycode(rest: &str) -> Result<KeyCode, KeymapError> {
+    match rest {
+        "" => Err(KeymapError::Parse("chord is missing a key".into())),
+        "Enter" | "enter" | "Return" | "return" => Ok(KeyCode::Enter),
+        "Tab" | "tab" => Ok(KeyCode::Tab),
+        "Esc" | "esc" | "Escape" | "escape" => Ok(KeyCode::Esc),
+        "Backspace" | "backspace" => Ok(KeyCode::Backspace),
+        "Up" | "up" => Ok(KeyCode::Up),
+        "Down" | "down" => Ok(KeyCode::Down),
+        "Left" | "left" => Ok(KeyCode::Left),
+        "Right" | "right" => Ok(KeyCode::Right),
+        s if s.chars().count() == 1 => Ok(KeyCode::Char(s.chars().next().unwrap())),
+        other => Err(KeymapError::Parse(format!("unknown key `{other}`"))),
+    }
+}
+
+/// Resolves the editor command a config file refers to by name.
+fn parse_command(name: &str) -> Result<Cmd, KeymapError> {
+    use rustyline::Cmd::*;
+    Ok(match name {
+        "history-search-forward" => HistorySearchForward,
+        "history-search-backward" => HistorySearchBackward,
+        "accept-line" => AcceptLine,
+        "interrupt" => Interrupt,
+        "clear-screen" => ClearScreen,
+        other => return Err(KeymapError::Parse(format!("unknown command `{other}`"))),
+    })
+}
+
+/// Parses a keymap config of `command = key sequence` lines (blank lines and `#`
+/// comments are ignored), e.g.:
+///
+/// ```text
+/// history-search-forward = M-n
+/// history-search-backward = M-p
+/// clear-screen = C-x C-l
+/// ```
+pub fn parse_config(source: &str) -> Result<Keymap, KeymapError> {
+    let mut keymap = Keymap::new();
+    for line in source.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, spec) = line
+            .split_once('=')
+            .ok_or_else(|| KeymapError::Parse(format!("expected `name = keys`, got `{line}`")))?;
+        keymap.bind(spec.trim(), parse_command(name.trim())?)?;
+    }
+    Ok(keymap)
+}
+
+/// The REPL's built-in key bindings, used when no config file is found.
+pub const DEFAULT_CONFIG: &str = "\
+history-search-forward = M-n
+history-search-backward = M-p
+";
+
+/// Loads the keymap from `path`, falling back to [`DEFAULT_CONFIG`] if the file
+/// doesn't exist.
+pub fn load(path: &str) -> Result<Keymap, KeymapError> {
+    match std::fs::read_to_string(path) {
+        Ok(source) => parse_config(&source),
+        Err(_) => parse_config(DEFAULT_CONFIG),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_chord_test() {
+        assert_eq!(parse_chord("M-n").unwrap(), KeyEvent::alt('n'));
+        assert_eq!(
+            parse_chord("<alt>n").unwrap(),
+            KeyEvent(KeyCode::Char('n'), Modifiers::ALT)
+        );
+        assert_eq!(
+            parse_chord("C-x").unwrap(),
+            KeyEvent(KeyCode::Char('x'), Modifiers::CTRL)
+        );
+        assert!(parse_chord("").is_err());
+        assert!(parse_chord("Q-z").is_err());
+    }
+
+    #[test]
+    fn bind_test() {
+        let mut keymap = Keymap::new();
+        keymap.bind("M-n", Cmd::HistorySearchForward).unwrap();
+        assert_eq!(
+            keymap.bind("M-n", Cmd::HistorySearchBackward),
+            Err(KeymapError::KeyAlreadySet)
+        );
+    }
+
+    #[test]
+    fn bind_rejects_blocked_path_test() {
+        let mut keymap = Keymap::new();
+        keymap.bind("C-x", Cmd::AcceptLine).unwrap();
+        assert_eq!(
+            keymap.bind("C-x C-s", Cmd::Interrupt),
+            Err(KeymapError::KeyPathBlocked)
+        );
+    }
+
+    #[test]
+    fn bind_rejects_shadowed_path_test() {
+        let mut keymap = Keymap::new();
+        keymap.bind("C-x C-s", Cmd::AcceptLine).unwrap();
+        assert_eq!(
+            keymap.bind("C-x", Cmd::Interrupt),
+            Err(KeymapError::KeyPathShadows)
+        );
+    }
+
+    #[test]
+    fn parse_config_test() {
+        let keymap = parse_config(DEFAULT_CONFIG).unwrap();
+        assert!(keymap
+            .root
+            .children
+            .iter()
+            .any(|(key, _)| *key == KeyEvent::alt('n')));
+    }
+
+    #[test]
+    fn parse_config_rejects_malformed_line_test() {
+        assert!(parse_config("not a valid line").is_err());
+        assert!(parse_config("history-search-forward = Q-z").is_err());
+    }
+}
@@ -10,32 +10,211 @@ use rustyline::completion::FilenameCompleter;
 use rustyline::error::ReadlineError;
 use rustyline::highlight::{Highlighter, MatchingBracketHighlighter};
 use rustyline::hint::HistoryHinter;
-use rustyline::validate::MatchingBracketValidator;
-use rustyline::{Cmd, CompletionType, Config, EditMode, Editor, KeyEvent};
+use rustyline::validate::{ValidationContext, ValidationResult};
+use rustyline::{CompletionType, Config, EditMode, Editor};
 use rustyline::{Completer, Helper, Hinter, Validator};
 
 use recap::{human, vm};
 
+mod keymap;
+
 type Span<'a> = nom_locate::LocatedSpan<&'a str>;
-struct ParseError(Diagnostic<()>);
+
+/// A byte span paired with the 1-indexed line/column `nom_locate` tracked for it, so
+/// diagnostics can be rendered either as `codespan` byte offsets or as the line/column
+/// coordinates editors and other tools expect.
+#[derive(Clone, Copy)]
+struct Position {
+    start: usize,
+    end: usize,
+    line: u32,
+    column: usize,
+}
+
+fn position_of(input: Span) -> Position {
+    Position {
+        start: input.location_offset(),
+        end: input.location_offset() + input.fragment().len(),
+        line: input.location_line(),
+        column: input.get_utf8_column(),
+    }
+}
+
+/// One frame of the "while parsing X" context stack built up by `append` as a nom
+/// error unwinds back out through the combinators that were trying to parse it.
+struct Context {
+    kind: ErrorKind,
+    span: Position,
+}
+
+/// A stable error code for an error kind, in the style of a compiler's `E1234`
+/// diagnostic codes, so tooling and docs can refer to a failure mode by name.
+fn error_code(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::Eof => "E0001",
+        ErrorKind::Digit | ErrorKind::TooLarge => "E0002",
+        ErrorKind::EscapedTransform => "E0003",
+        ErrorKind::Alpha => "E0004",
+        ErrorKind::Char | ErrorKind::OneOf => "E0005",
+        _ => "E0000",
+    }
+}
+
+/// An optional "help:" note to attach for a given error kind.
+fn help_for(kind: ErrorKind) -> Option<&'static str> {
+    match kind {
+        ErrorKind::Digit => Some("numeric literals may not start with a leading zero"),
+        ErrorKind::TooLarge => Some("value is out of range for its type"),
+        ErrorKind::EscapedTransform => {
+            Some(r#"string literals support \n, \t, \\, \", and \u{...} escapes"#)
+        }
+        _ => None,
+    }
+}
+
+struct ParseError {
+    code: &'static str,
+    message: String,
+    // Innermost-first stack of the contexts `append` wrapped this error in as it
+    // propagated back out through the combinators that were trying to parse it.
+    contexts: Vec<Context>,
+    help: Vec<String>,
+    notes: Vec<String>,
+    // The innermost error kind, preserved through `append`'s context stacking so callers
+    // can tell e.g. "ran out of input" (`ErrorKind::Eof`) apart from a genuine mismatch.
+    kind: ErrorKind,
+    primary_span: Position,
+}
 struct File(SimpleFile<&'static str, String>);
 
+impl ParseError {
+    fn is_incomplete(&self) -> bool {
+        self.kind == ErrorKind::Eof
+    }
+
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    fn to_diagnostic(&self) -> Diagnostic<()> {
+        let mut labels = vec![Label::primary((), self.primary_span.start..self.primary_span.end)
+            .with_message(self.message.clone())];
+        labels.extend(self.contexts.iter().map(|ctx| {
+            Label::secondary((), ctx.span.start..ctx.span.end)
+                .with_message(format!("while parsing {}", ctx.kind.description()))
+        }));
+
+        let mut notes: Vec<String> = self
+            .help
+            .iter()
+            .map(|help| format!("help: {help}"))
+            .collect();
+        notes.extend(self.notes.iter().map(|note| format!("note: {note}")));
+
+        Diagnostic::error()
+            .with_code(self.code)
+            .with_message(&self.message)
+            .with_labels(labels)
+            .with_notes(notes)
+    }
+
+    /// Serializes this diagnostic to a single-line JSON object: span byte range,
+    /// line/column, severity, message, error code, and secondary labels. One object
+    /// per diagnostic, so downstream tools (editors, test harnesses) can consume
+    /// recap errors programmatically instead of scraping colored terminal output.
+    fn to_json(&self) -> String {
+        let labels: Vec<String> = self
+            .contexts
+            .iter()
+            .map(|ctx| {
+                format!(
+                    "{{\"message\":{},\"span\":{}}}",
+                    json_string(&format!("while parsing {}", ctx.kind.description())),
+                    json_span(ctx.span)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"severity\":\"error\",\"code\":\"{}\",\"message\":{},\"span\":{},\"labels\":[{}]}}",
+            self.code,
+            json_string(&self.message),
+            json_span(self.primary_span),
+            labels.join(","),
+        )
+    }
+}
+
+fn json_span(span: Position) -> String {
+    format!(
+        "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+        span.start, span.end, span.line, span.column
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 impl<'a> nom::error::ParseError<Span<'a>> for ParseError {
     fn from_error_kind(input: Span<'a>, kind: ErrorKind) -> Self {
-        let start = input.location_offset();
-        let end = start + input.fragment().len();
-        let message = format!("expected {}", kind.description());
-        Self(
-            Diagnostic::error()
-                .with_message("parse error")
-                .with_labels(vec![Label::primary((), start..end).with_message(message)]),
-        )
+        Self {
+            code: error_code(kind),
+            message: format!("expected {}", kind.description()),
+            contexts: Vec::new(),
+            help: help_for(kind).into_iter().map(String::from).collect(),
+            notes: Vec::new(),
+            kind,
+            primary_span: position_of(input),
+        }
     }
-    fn append(input: Span<'a>, kind: ErrorKind, other: Self) -> Self {
-        let start = input.location_offset();
-        let end = start + input.fragment().len();
-        let message = format!("while expecting {}", kind.description());
-        Self(other.with_labels(vec![Label::secondary((), start..end).with_message(message)]))
+    fn append(input: Span<'a>, kind: ErrorKind, mut other: Self) -> Self {
+        other.contexts.push(Context {
+            kind,
+            span: position_of(input),
+        });
+        other
+    }
+}
+
+/// Validates REPL input by running the recap tokenizer over it, rather than just
+/// balancing brackets: an unterminated string or an unterminated `#+BEGIN_...` block
+/// keeps the prompt open for more lines exactly when recap's own grammar would, and a
+/// genuine mismatch is reported with the message of the diagnostic the REPL would
+/// otherwise print after submit.
+///
+/// This only catches incompleteness the *tokenizer* can see. A line that tokenizes
+/// cleanly but is grammatically incomplete — a dangling `1 +` with nothing after it —
+/// still submits immediately: there's no expression-level parser yet to tell "this
+/// needs another line" apart from "this is already a complete, if nonsensical,
+/// program".
+struct RecapValidator;
+
+impl rustyline::validate::Validator for RecapValidator {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        for token in human::tokenize::<ParseError>(Span::new(input)) {
+            if let Err(e) = token {
+                return Ok(if e.is_incomplete() {
+                    ValidationResult::Incomplete
+                } else {
+                    ValidationResult::Invalid(Some(e.message()))
+                });
+            }
+        }
+        Ok(ValidationResult::Valid(None))
     }
 }
 
@@ -45,7 +224,7 @@ struct MyHelper {
     completer: FilenameCompleter,
     highlighter: MatchingBracketHighlighter,
     #[rustyline(Validator)]
-    validator: MatchingBracketValidator,
+    validator: RecapValidator,
     #[rustyline(Hinter)]
     hinter: HistoryHinter,
     colored_prompt: String,
@@ -93,12 +272,14 @@ fn main() -> Result<()> {
         highlighter: MatchingBracketHighlighter::new(),
         hinter: HistoryHinter {},
         colored_prompt: "".to_owned(),
-        validator: MatchingBracketValidator::new(),
+        validator: RecapValidator,
     };
     let mut rl = Editor::with_config(config)?;
     rl.set_helper(Some(h));
-    rl.bind_sequence(KeyEvent::alt('n'), Cmd::HistorySearchForward);
-    rl.bind_sequence(KeyEvent::alt('p'), Cmd::HistorySearchBackward);
+    match keymap::load("keymap.conf") {
+        Ok(keymap) => keymap.register(&mut rl),
+        Err(e) => eprintln!("keymap.conf: {e}, using built-in bindings"),
+    }
     if rl.load_history("history.txt").is_err() {
         println!("No previous history.");
     }
@@ -108,9 +289,14 @@ fn main() -> Result<()> {
     let csr_writer = StandardStream::stdout(ColorChoice::Always);
     let csr_config = codespan_reporting::term::Config::default();
 
+    // RECAP_DIAGNOSTICS=json switches diagnostics from the human-readable codespan
+    // report to one JSON object per line, for editors and other tools to consume.
+    let json_diagnostics = std::env::var("RECAP_DIAGNOSTICS").as_deref() == Ok("json");
+
     // Configure recap
     let mut memory = [MaybeUninit::uninit(); 100];
-    let _ = vm::Machine::new(&mut memory);
+    let mut host = vm::BasicHost;
+    let _ = vm::Machine::new(&mut memory, &mut host);
 
     loop {
         let p = format!("{count}> ");
@@ -124,12 +310,22 @@ fn main() -> Result<()> {
                 for token in human::tokenize::<ParseError>(Span::new(file.source())) {
                     match token {
                         Ok(span) => println!("{span:?}"),
-                        Err(e) => codespan_reporting::term::emit(
-                            &mut csr_writer.lock(),
-                            &csr_config,
-                            &file,
-                            &e.0,
-                        )?,
+                        Err(e) if json_diagnostics => println!("{}", e.to_json()),
+                        Err(e) => {
+                            codespan_reporting::term::emit(
+                                &mut csr_writer.lock(),
+                                &csr_config,
+                                &file,
+                                &e.to_diagnostic(),
+                            )?;
+                            // This binary's own Cargo.toml must declare
+                            // `trace = ["recap/trace"]` for `--features trace` to reach
+                            // this gate; with `recap`'s own `trace` feature alone, the
+                            // REPL's calls are still recorded, but this print is
+                            // compiled out and the log is never shown.
+                            #[cfg(feature = "trace")]
+                            print!("{}", human::trace::render(&human::trace::take_log()));
+                        }
                     }
                 }
             }